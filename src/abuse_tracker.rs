@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use crate::config::AbuseConfig;
+
+#[derive(Debug, Clone)]
+struct ClientRecord {
+    error_count: u32,
+    window_start: Instant,
+    cooldown_until: Option<Instant>,
+}
+
+/// Per-peer reputation tracker: counts structurally-invalid submissions within a
+/// fixed window that starts at a peer's first counted error (and restarts with
+/// the first error after it expires), and short-circuits a peer with `resource_exhausted` once it
+/// crosses `error_threshold`, protecting the single state lock from abusive clients.
+#[derive(Debug, Clone)]
+pub struct AbuseTracker {
+    config: AbuseConfig,
+    clients: Arc<Mutex<HashMap<SocketAddr, ClientRecord>>>,
+}
+
+impl AbuseTracker {
+    pub fn new(config: AbuseConfig) -> Self {
+        Self {
+            config,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the remaining cooldown if this peer is currently throttled.
+    pub async fn check(&self, peer: SocketAddr) -> Option<Duration> {
+        let clients = self.clients.lock().await;
+        let cooldown_until = clients.get(&peer)?.cooldown_until?;
+
+        let now = Instant::now();
+        (now < cooldown_until).then(|| cooldown_until - now)
+    }
+
+    /// Records a structurally-invalid submission from this peer, tripping a
+    /// cooldown once `error_threshold` is crossed within `window_secs`.
+    pub async fn record_error(&self, peer: SocketAddr) {
+        let mut clients = self.clients.lock().await;
+        let now = Instant::now();
+        let window = Duration::from_secs(self.config.window_secs);
+
+        let record = clients.entry(peer).or_insert(ClientRecord {
+            error_count: 0,
+            window_start: now,
+            cooldown_until: None,
+        });
+
+        if now.duration_since(record.window_start) > window {
+            record.error_count = 0;
+            record.window_start = now;
+        }
+
+        record.error_count += 1;
+
+        if record.error_count >= self.config.error_threshold {
+            record.cooldown_until = Some(now + Duration::from_secs(self.config.cooldown_secs));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[tokio::test]
+    async fn test_check_is_clear_for_an_unseen_peer() {
+        let tracker = AbuseTracker::new(AbuseConfig {
+            error_threshold: 3,
+            window_secs: 60,
+            cooldown_secs: 60,
+        });
+
+        assert_eq!(tracker.check(peer(1)).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_record_error_below_threshold_does_not_trip_cooldown() {
+        let tracker = AbuseTracker::new(AbuseConfig {
+            error_threshold: 3,
+            window_secs: 60,
+            cooldown_secs: 60,
+        });
+
+        tracker.record_error(peer(2)).await;
+        tracker.record_error(peer(2)).await;
+
+        assert_eq!(tracker.check(peer(2)).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_record_error_at_threshold_trips_cooldown() {
+        let tracker = AbuseTracker::new(AbuseConfig {
+            error_threshold: 3,
+            window_secs: 60,
+            cooldown_secs: 60,
+        });
+
+        tracker.record_error(peer(3)).await;
+        tracker.record_error(peer(3)).await;
+        tracker.record_error(peer(3)).await;
+
+        let remaining = tracker.check(peer(3)).await;
+        assert!(remaining.is_some());
+        assert!(remaining.unwrap() <= Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_expires_after_its_duration() {
+        let tracker = AbuseTracker::new(AbuseConfig {
+            error_threshold: 1,
+            window_secs: 60,
+            cooldown_secs: 0,
+        });
+
+        tracker.record_error(peer(4)).await;
+
+        // cooldown_secs: 0 means `cooldown_until` is already in the past
+        assert_eq!(tracker.check(peer(4)).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_window_expiry_resets_the_error_count() {
+        let tracker = AbuseTracker::new(AbuseConfig {
+            error_threshold: 2,
+            window_secs: 0,
+            cooldown_secs: 60,
+        });
+
+        tracker.record_error(peer(5)).await;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        // window_secs: 0 means this starts a fresh window rather than accumulating
+        // onto the first error, so the threshold is never crossed
+        tracker.record_error(peer(5)).await;
+
+        assert_eq!(tracker.check(peer(5)).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_peers_are_tracked_independently() {
+        let tracker = AbuseTracker::new(AbuseConfig {
+            error_threshold: 1,
+            window_secs: 60,
+            cooldown_secs: 60,
+        });
+
+        tracker.record_error(peer(6)).await;
+
+        assert!(tracker.check(peer(6)).await.is_some());
+        assert_eq!(tracker.check(peer(7)).await, None);
+    }
+}
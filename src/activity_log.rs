@@ -0,0 +1,167 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Outcome of an `add_trade` call, recorded for audit and replay. Calls to
+/// `evaluate_trade` alone are not recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Decision {
+    Allowed,
+    Rejected(String),
+}
+
+/// A single accepted or rejected trade decision, appended to the activity log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Activity {
+    pub timestamp: i64,
+    pub asset: String,
+    pub side: i32,
+    pub quantity: f64,
+    pub decision: Decision,
+    pub resulting_position: f64,
+}
+
+/// Append-only JSONL activity log, durable across restarts so `RisklyState`
+/// can be rebuilt from it instead of an external database.
+#[derive(Debug, Clone)]
+pub struct ActivityLog {
+    path: Option<PathBuf>,
+}
+
+impl ActivityLog {
+    pub fn new(activity_log_path: Option<String>) -> Self {
+        Self {
+            path: activity_log_path.map(PathBuf::from),
+        }
+    }
+
+    pub fn append(&self, activity: &Activity) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let line = match serde_json::to_string(activity) {
+            Ok(line) => line,
+            Err(error) => {
+                println!("Failed to serialize activity log entry: {error}");
+                return;
+            }
+        };
+
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{line}"));
+
+        if let Err(error) = result {
+            println!("Failed to persist activity log entry to {path:?}: {error}");
+        }
+    }
+
+    /// Reads every persisted activity, oldest first. Malformed lines are skipped.
+    pub fn read_all(&self) -> Vec<Activity> {
+        let Some(path) = &self.path else {
+            return vec![];
+        };
+
+        let Ok(contents) = fs::read_to_string(path) else {
+            return vec![];
+        };
+
+        contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    /// Reads activities matching an optional asset filter and inclusive time range,
+    /// for `get_activities`.
+    pub fn read_range(
+        &self,
+        asset: Option<&str>,
+        start_timestamp: Option<i64>,
+        end_timestamp: Option<i64>,
+    ) -> Vec<Activity> {
+        self.read_all()
+            .into_iter()
+            .filter(|activity| asset.map_or(true, |asset| activity.asset == asset))
+            .filter(|activity| start_timestamp.map_or(true, |start| activity.timestamp >= start))
+            .filter(|activity| end_timestamp.map_or(true, |end| activity.timestamp <= end))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "riskly_activity_log_test_{name}_{}.jsonl",
+            std::process::id()
+        ))
+    }
+
+    fn activity(timestamp: i64, asset: &str, decision: Decision) -> Activity {
+        Activity {
+            timestamp,
+            asset: asset.to_string(),
+            side: 0,
+            quantity: 1.5,
+            decision,
+            resulting_position: 1.5,
+        }
+    }
+
+    #[test]
+    fn test_append_then_read_all_round_trips_in_order() {
+        let path = temp_log_path("round_trip");
+        let _ = fs::remove_file(&path);
+        let log = ActivityLog::new(Some(path.to_string_lossy().to_string()));
+
+        log.append(&activity(1, "BTC", Decision::Allowed));
+        log.append(&activity(2, "ETH", Decision::Rejected("too large".to_string())));
+
+        let activities = log.read_all();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0].asset, "BTC");
+        assert!(matches!(activities[0].decision, Decision::Allowed));
+        assert_eq!(activities[1].asset, "ETH");
+        assert!(matches!(activities[1].decision, Decision::Rejected(ref reason) if reason == "too large"));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_all_without_a_path_returns_empty() {
+        let log = ActivityLog::new(None);
+        assert_eq!(log.read_all().len(), 0);
+    }
+
+    #[test]
+    fn test_read_range_filters_by_asset_and_timestamp() {
+        let path = temp_log_path("read_range");
+        let _ = fs::remove_file(&path);
+        let log = ActivityLog::new(Some(path.to_string_lossy().to_string()));
+
+        log.append(&activity(1, "BTC", Decision::Allowed));
+        log.append(&activity(2, "ETH", Decision::Allowed));
+        log.append(&activity(3, "BTC", Decision::Allowed));
+
+        let btc_only = log.read_range(Some("BTC"), None, None);
+        assert_eq!(btc_only.len(), 2);
+        assert!(btc_only.iter().all(|activity| activity.asset == "BTC"));
+
+        let from_two = log.read_range(None, Some(2), None);
+        assert_eq!(from_two.len(), 2);
+
+        let up_to_one = log.read_range(None, None, Some(1));
+        assert_eq!(up_to_one.len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -3,6 +3,47 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
+/// Runtime trading posture, toggled via `disable_trading`/`enable_trading` on top of
+/// whatever `RisklyConfig::trading_mode` started the service in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingMode {
+    /// Normal operation: all risk checks apply as usual.
+    Enabled,
+    /// Maintenance/incident mode: only trades that move a position toward zero are allowed.
+    ReduceOnly,
+    /// Hard stop: every trade is rejected.
+    Disabled,
+}
+
+/// Thresholds for throttling a peer that repeatedly submits structurally-invalid trades.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AbuseConfig {
+    /// Number of structurally-invalid submissions within `window_secs` that trips the cooldown
+    pub error_threshold: u32,
+
+    /// Window, in seconds, over which `error_threshold` is counted. It starts at a
+    /// peer's first counted error and restarts with the first error after it expires.
+    pub window_secs: u64,
+
+    /// How long, in seconds, a peer is short-circuited once throttled
+    pub cooldown_secs: u64,
+}
+
+/// Where to source live market prices from, rather than relying solely on clients
+/// calling `update_market_value`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceFeedConfig {
+    /// Base URL of the price feed (streamed first, polled as a fallback)
+    pub url: String,
+
+    /// Assets to subscribe to / poll for
+    pub assets: Vec<String>,
+
+    /// Polling cadence used while no streaming connection is established
+    pub poll_interval_secs: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RisklyConfig {
     /// Max allowed position per asset (absolute units, e.g., 1.5 BTC)
@@ -23,8 +64,31 @@ pub struct RisklyConfig {
     /// Maximum slippage allowed (in %, e.g., 0.5 means 0.5%)
     pub max_slippage_pct: f64,
 
-    /// Hard stop toggle (e.g., false = trading disabled)
-    pub trading_enabled: bool,
+    /// Trading posture to start the service in (enabled, reduce_only, or disabled)
+    pub trading_mode: TradingMode,
+
+    /// Wall-clock time of day at which `daily_volume` automatically resets (e.g., "00:00")
+    pub daily_reset_time: String,
+
+    /// IANA timezone name the daily reset boundary is computed in (e.g., "America/New_York")
+    pub reset_timezone: String,
+
+    /// Path to the append-only JSONL activity log. When unset, no activity is persisted.
+    #[serde(default)]
+    pub activity_log_path: Option<String>,
+
+    /// Whether to replay `activity_log_path` on startup to rebuild `RisklyState`
+    /// (crash recovery). Ignored if `activity_log_path` is unset.
+    #[serde(default)]
+    pub replay_activity_log_on_startup: bool,
+
+    /// Per-peer abuse accounting for structurally-invalid submissions
+    pub abuse: AbuseConfig,
+
+    /// Optional outbound subscriber that keeps `last_prices` fresh without relying
+    /// on clients calling `update_market_value`
+    #[serde(default)]
+    pub price_feed: Option<PriceFeedConfig>,
 
     /// Address to listen on (e.g., "127.0.0.1:50051")
     pub listen_address: String,
@@ -52,14 +116,17 @@ mod tests {
             "max_allocation_per_asset_pct": {"BTC": 50.0, "ETH": 30.0},
             "allowed_assets": ["BTC", "ETH"],
             "max_slippage_pct": 0.5,
-            "trading_enabled": true,
+            "trading_mode": "enabled",
+            "daily_reset_time": "00:00",
+            "reset_timezone": "UTC",
+            "abuse": {"error_threshold": 5, "window_secs": 60, "cooldown_secs": 300},
             "listen_address": "127.0.0.1:50051"
         }
         "#;
         let config: RisklyConfig = serde_json::from_str(json).unwrap();
         assert_eq!(config.max_position_per_asset["BTC"], 2.0);
         assert_eq!(config.allowed_assets, vec!["BTC", "ETH"]);
-        assert!(config.trading_enabled);
+        assert_eq!(config.trading_mode, TradingMode::Enabled);
         assert_eq!(config.listen_address, "127.0.0.1:50051");
     }
 }
@@ -1,4 +1,6 @@
 use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::transport::Server;
 use tonic::{async_trait, Response};
 
@@ -11,7 +13,10 @@ pub mod riskly {
     tonic::include_proto!("riskly");
 }
 
+pub mod abuse_tracker;
+pub mod activity_log;
 pub mod config;
+pub mod price_feed;
 pub mod riskly_error;
 pub mod riskly_service;
 
@@ -22,8 +27,18 @@ impl Riskly for RisklyService {
         trade: tonic::Request<riskly::Trade>,
     ) -> Result<tonic::Response<riskly::TradeEvaluationResponse>, tonic::Status> {
         let start_time = Instant::now();
+        let peer = trade.remote_addr();
+
+        if let Some(peer) = peer {
+            if let Some(remaining) = self.abuse_tracker.check(peer).await {
+                return Err(tonic::Status::resource_exhausted(format!(
+                    "too many invalid submissions, retry in {remaining:?}"
+                )));
+            }
+        }
+
         let trade_inner = trade.into_inner();
-        
+
         let result = match self.evaluate_trade(trade_inner).await {
             Ok(()) => {
                 Ok(Response::new(TradeEvaluationResponse {
@@ -32,38 +47,103 @@ impl Riskly for RisklyService {
                 }))
             }
             Err(err) => {
+                if let Some(peer) = peer {
+                    if err.is_abusive() {
+                        self.abuse_tracker.record_error(peer).await;
+                    }
+                }
+
                 Ok(Response::new(TradeEvaluationResponse {
                     allowed: false,
                     reason: err.to_string(),
                 }))
             }
         };
-        
+
         let duration = start_time.elapsed();
         println!("evaluate_trade endpoint took: {:?}", duration);
-        
+
         result
     }
 
     async fn add_trade(
         &self,
-        _request: tonic::Request<riskly::Trade>,
+        request: tonic::Request<riskly::Trade>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        let peer = request.remote_addr();
+
+        if let Some(peer) = peer {
+            if let Some(remaining) = self.abuse_tracker.check(peer).await {
+                return Err(tonic::Status::resource_exhausted(format!(
+                    "too many invalid submissions, retry in {remaining:?}"
+                )));
+            }
+        }
+
+        match self.add_trade(request.into_inner()).await {
+            Ok(()) => Ok(Response::new(riskly::Ack {
+                success: true,
+                message: "trade added".to_string(),
+            })),
+            Err(err) => {
+                if let Some(peer) = peer {
+                    if err.is_abusive() {
+                        self.abuse_tracker.record_error(peer).await;
+                    }
+                }
+
+                Ok(Response::new(riskly::Ack {
+                    success: false,
+                    message: err.to_string(),
+                }))
+            }
+        }
     }
 
     async fn add_order(
         &self,
-        _request: tonic::Request<riskly::OpenOrder>,
+        request: tonic::Request<riskly::OpenOrder>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        let peer = request.remote_addr();
+
+        if let Some(peer) = peer {
+            if let Some(remaining) = self.abuse_tracker.check(peer).await {
+                return Err(tonic::Status::resource_exhausted(format!(
+                    "too many invalid submissions, retry in {remaining:?}"
+                )));
+            }
+        }
+
+        match self.add_order(request.into_inner()).await {
+            Ok(()) => Ok(Response::new(riskly::Ack {
+                success: true,
+                message: "order added".to_string(),
+            })),
+            Err(err) => {
+                if let Some(peer) = peer {
+                    if err.is_abusive() {
+                        self.abuse_tracker.record_error(peer).await;
+                    }
+                }
+
+                Ok(Response::new(riskly::Ack {
+                    success: false,
+                    message: err.to_string(),
+                }))
+            }
+        }
     }
 
     async fn remove_order(
         &self,
-        _request: tonic::Request<riskly::RemoveOrderRequest>,
+        request: tonic::Request<riskly::RemoveOrderRequest>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        self.remove_order(request.into_inner().order_id).await;
+
+        Ok(Response::new(riskly::Ack {
+            success: true,
+            message: "order removed".to_string(),
+        }))
     }
 
     async fn get_state(
@@ -94,6 +174,48 @@ impl Riskly for RisklyService {
         unimplemented!()
     }
 
+    type GetActivitiesStream =
+        tokio_stream::wrappers::ReceiverStream<Result<riskly::ActivityRecord, tonic::Status>>;
+
+    async fn get_activities(
+        &self,
+        request: tonic::Request<riskly::GetActivitiesRequest>,
+    ) -> Result<tonic::Response<Self::GetActivitiesStream>, tonic::Status> {
+        let query = request.into_inner();
+        let activities = self.activity_log.read_range(
+            query.asset.as_deref(),
+            query.start_timestamp,
+            query.end_timestamp,
+        );
+
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            for activity in activities {
+                let (allowed, reason) = match activity.decision {
+                    crate::activity_log::Decision::Allowed => (true, "allowed".to_string()),
+                    crate::activity_log::Decision::Rejected(reason) => (false, reason),
+                };
+
+                let record = riskly::ActivityRecord {
+                    timestamp: activity.timestamp,
+                    asset: activity.asset,
+                    side: activity.side,
+                    quantity: activity.quantity,
+                    allowed,
+                    reason,
+                    resulting_position: activity.resulting_position,
+                };
+
+                if tx.send(Ok(record)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
     type StreamStateStream =
         tokio_stream::wrappers::ReceiverStream<Result<riskly::RisklyState, tonic::Status>>;
 
@@ -101,42 +223,83 @@ impl Riskly for RisklyService {
         &self,
         _request: tonic::Request<riskly::Empty>,
     ) -> Result<tonic::Response<Self::StreamStateStream>, tonic::Status> {
-        unimplemented!()
+        let mut state_rx = self.state_rx.clone();
+        let (tx, rx) = mpsc::channel(16);
+
+        // Send the current snapshot immediately so a late subscriber isn't
+        // blind until the next add_trade/add_order mutation.
+        let snapshot = state_rx.borrow_and_update().clone();
+        if tx.send(snapshot).await.is_err() {
+            return Ok(Response::new(ReceiverStream::new(rx)));
+        }
+
+        tokio::spawn(async move {
+            while state_rx.changed().await.is_ok() {
+                let snapshot = state_rx.borrow_and_update().clone();
+                if tx.send(snapshot).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
     }
 
     async fn reset_daily_limits(
         &self,
         _request: tonic::Request<riskly::Empty>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        self.reset_daily_limits().await;
+
+        Ok(Response::new(riskly::Ack {
+            success: true,
+            message: "daily limits reset".to_string(),
+        }))
     }
 
     async fn update_market_value(
         &self,
-        _request: tonic::Request<riskly::PriceUpdateRequest>,
+        request: tonic::Request<riskly::PriceUpdateRequest>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        self.update_market_value(request.into_inner()).await;
+
+        Ok(Response::new(riskly::Ack {
+            success: true,
+            message: "market value updated".to_string(),
+        }))
     }
 
     async fn disable_trading(
         &self,
         _request: tonic::Request<riskly::Empty>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        self.disable_trading().await;
+
+        Ok(Response::new(riskly::Ack {
+            success: true,
+            message: "trading disabled".to_string(),
+        }))
     }
 
     async fn enable_trading(
         &self,
         _request: tonic::Request<riskly::Empty>,
     ) -> Result<tonic::Response<riskly::Ack>, tonic::Status> {
-        unimplemented!()
+        self.enable_trading().await;
+
+        Ok(Response::new(riskly::Ack {
+            success: true,
+            message: "trading enabled".to_string(),
+        }))
     }
 
     async fn is_trading_enabled(
         &self,
         _request: tonic::Request<riskly::Empty>,
     ) -> Result<tonic::Response<riskly::TradingStatusResponse>, tonic::Status> {
-        unimplemented!()
+        Ok(Response::new(riskly::TradingStatusResponse {
+            enabled: self.is_trading_enabled().await,
+        }))
     }
 }
 
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::sync::{watch, Mutex};
+
+use crate::config::PriceFeedConfig;
+use crate::riskly::RisklyState;
+
+const MAX_BACKOFF_SECS: u64 = 60;
+
+#[derive(Debug, Deserialize)]
+struct PriceTick {
+    asset: String,
+    price: f64,
+}
+
+/// Keeps `RisklyState.last_prices` fresh from an outbound feed: prefers a push
+/// stream from `{url}/stream`, and degrades to polling `{url}/prices` on
+/// `poll_interval_secs` when the stream is unavailable or drops. Each fallback
+/// polls once and then retries the stream subscription: after a successful poll
+/// the retry waits `poll_interval_secs`, after a failed poll it waits an
+/// exponential backoff (1s doubling up to 60s, reset once a stream subscribes).
+pub fn spawn_price_feed_task(
+    state: Arc<Mutex<RisklyState>>,
+    state_tx: watch::Sender<Result<RisklyState, tonic::Status>>,
+    config: PriceFeedConfig,
+) {
+    tokio::spawn(async move {
+        let poll_interval = Duration::from_secs(config.poll_interval_secs.max(1));
+        let mut backoff_secs = 1;
+
+        loop {
+            match stream_prices(&config).await {
+                Ok(mut ticks) => {
+                    println!("Price feed: subscribed to stream at {}", config.url);
+                    backoff_secs = 1;
+
+                    while let Some(tick) = ticks.next().await {
+                        match tick {
+                            Ok(tick) => apply_tick(&state, &state_tx, tick).await,
+                            Err(error) => {
+                                println!("Price feed: stream read error: {error}");
+                                break;
+                            }
+                        }
+                    }
+
+                    println!("Price feed: stream to {} dropped, degrading to polling", config.url);
+                }
+                Err(error) => {
+                    println!(
+                        "Price feed: stream to {} unavailable ({error}), polling instead",
+                        config.url
+                    );
+                }
+            }
+
+            match poll_prices(&config).await {
+                Ok(ticks) => {
+                    for tick in ticks {
+                        apply_tick(&state, &state_tx, tick).await;
+                    }
+                    tokio::time::sleep(poll_interval).await;
+                }
+                Err(error) => {
+                    println!("Price feed: poll of {} failed: {error}", config.url);
+                    tokio::time::sleep(Duration::from_secs(backoff_secs)).await;
+                    backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
+                }
+            }
+        }
+    });
+}
+
+async fn stream_prices(
+    config: &PriceFeedConfig,
+) -> Result<impl futures_util::Stream<Item = Result<PriceTick, reqwest::Error>>, reqwest::Error> {
+    let response = reqwest::Client::new()
+        .get(format!("{}/stream", config.url))
+        .query(&[("assets", config.assets.join(","))])
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(response
+        .bytes_stream()
+        .map(|chunk| chunk.map(|bytes| bytes.to_vec()))
+        .filter_map(|chunk| async move {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(error) => return Some(Err(error)),
+            };
+
+            serde_json::from_slice::<PriceTick>(&chunk)
+                .ok()
+                .map(Ok)
+        }))
+}
+
+async fn poll_prices(config: &PriceFeedConfig) -> Result<Vec<PriceTick>, reqwest::Error> {
+    reqwest::Client::new()
+        .get(format!("{}/prices", config.url))
+        .query(&[("assets", config.assets.join(","))])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<PriceTick>>()
+        .await
+}
+
+async fn apply_tick(
+    state: &Arc<Mutex<RisklyState>>,
+    state_tx: &watch::Sender<Result<RisklyState, tonic::Status>>,
+    tick: PriceTick,
+) {
+    let mut current_state = state.lock().await;
+    current_state.last_prices.insert(tick.asset, tick.price);
+
+    if let Err(error) = state_tx.send(Ok(current_state.clone())) {
+        println!("Channel send error {error:?}");
+    }
+}
@@ -6,6 +6,25 @@ pub enum RisklyError {
     ExceedsMaxPosition(String),
     ExceedsDailyVolume(String),
     ExceedsMaxAllocation(String),
+    ExceedsMaxSlippage(String),
+    InvalidTradeSide(String),
+    TradingDisabled(String),
+    ReduceOnlyViolation(String),
+    InvalidQuantity(String),
+}
+
+impl RisklyError {
+    /// Whether this rejection reflects a structurally-invalid submission (bad peer
+    /// input) rather than a legitimate risk-based rejection. Used to drive
+    /// per-peer abuse accounting without penalizing normal risk rejections.
+    pub fn is_abusive(&self) -> bool {
+        matches!(
+            self,
+            RisklyError::DisallowedAsset(_)
+                | RisklyError::InvalidTradeSide(_)
+                | RisklyError::InvalidQuantity(_)
+        )
+    }
 }
 
 impl fmt::Display for RisklyError {
@@ -18,6 +37,11 @@ impl fmt::Display for RisklyError {
             RisklyError::ExceedsMaxPosition(msg) => write!(f, "Exceeds max position: {msg}"),
             RisklyError::ExceedsDailyVolume(msg) => write!(f, "Exceeds daily volume: {msg}"),
             RisklyError::ExceedsMaxAllocation(msg) => write!(f, "Exceeds max allocation: {msg}"),
+            RisklyError::ExceedsMaxSlippage(msg) => write!(f, "Exceeds max slippage: {msg}"),
+            RisklyError::InvalidTradeSide(msg) => write!(f, "Invalid trade side: {msg}"),
+            RisklyError::TradingDisabled(msg) => write!(f, "Trading disabled: {msg}"),
+            RisklyError::ReduceOnlyViolation(msg) => write!(f, "Reduce-only violation: {msg}"),
+            RisklyError::InvalidQuantity(msg) => write!(f, "Invalid quantity: {msg}"),
         }
     }
 }
@@ -1,10 +1,18 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
+use chrono::{NaiveTime, TimeZone};
+use chrono_tz::Tz;
 use tokio::sync::{watch, Mutex};
 
 use crate::{
-    config::RisklyConfig,
-    riskly::{RisklyState, Trade},
+    abuse_tracker::AbuseTracker,
+    activity_log::{Activity, ActivityLog, Decision},
+    config::{RisklyConfig, TradingMode},
+    riskly::{OpenOrder, PriceUpdateRequest, RisklyState, Trade},
     riskly_error::RisklyError,
 };
 
@@ -14,26 +22,111 @@ pub struct RisklyService {
     pub state: Arc<Mutex<RisklyState>>,
     pub state_rx: watch::Receiver<Result<RisklyState, tonic::Status>>,
     pub state_tx: watch::Sender<Result<RisklyState, tonic::Status>>,
+    pub trading_mode: Arc<Mutex<TradingMode>>,
+    pub activity_log: ActivityLog,
+    pub abuse_tracker: AbuseTracker,
 }
 
 impl RisklyService {
     pub fn new(config: RisklyConfig) -> Self {
-        let state = RisklyState {
+        let mut state = RisklyState {
             current_positions: HashMap::new(),
             open_orders: vec![],
             daily_volume: HashMap::new(),
+            last_prices: HashMap::new(),
+            mark_timestamps: HashMap::new(),
         };
 
+        let activity_log = ActivityLog::new(config.activity_log_path.clone());
+        if config.replay_activity_log_on_startup {
+            let daily_window_start =
+                start_of_current_reset_window(&config.daily_reset_time, &config.reset_timezone);
+
+            for activity in activity_log.read_all() {
+                if let Decision::Allowed = activity.decision {
+                    state
+                        .current_positions
+                        .insert(activity.asset.clone(), activity.resulting_position);
+
+                    if activity.timestamp >= daily_window_start {
+                        *state.daily_volume.entry(activity.asset).or_insert(0.0) +=
+                            activity.quantity;
+                    }
+                }
+            }
+        }
+
         let (state_tx, state_rx) = watch::channel(Ok(state.clone()));
+        let trading_mode = Arc::new(Mutex::new(config.trading_mode));
+        let abuse_tracker = AbuseTracker::new(config.abuse);
+        let state = Arc::new(Mutex::new(state));
+
+        spawn_daily_reset_task(
+            state.clone(),
+            state_tx.clone(),
+            config.daily_reset_time.clone(),
+            config.reset_timezone.clone(),
+        );
+
+        if let Some(price_feed) = config.price_feed.clone() {
+            crate::price_feed::spawn_price_feed_task(state.clone(), state_tx.clone(), price_feed);
+        }
 
         Self {
             config: Arc::new(config),
-            state: Arc::new(Mutex::new(state)),
+            state,
             state_rx,
             state_tx,
+            trading_mode,
+            activity_log,
+            abuse_tracker,
         }
     }
 
+    fn log_activity(
+        &self,
+        asset: String,
+        side: i32,
+        quantity: f64,
+        decision: Decision,
+        resulting_position: f64,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as i64)
+            .unwrap_or_default();
+
+        self.activity_log.append(&Activity {
+            timestamp,
+            asset,
+            side,
+            quantity,
+            decision,
+            resulting_position,
+        });
+    }
+
+    pub async fn reset_daily_limits(&self) {
+        let mut current_state = self.state.lock().await;
+        current_state.daily_volume.clear();
+
+        if let Err(error) = self.state_tx.send(Ok(current_state.clone())) {
+            println!("Channel send error {error:?}");
+        }
+    }
+
+    pub async fn disable_trading(&self) {
+        *self.trading_mode.lock().await = TradingMode::Disabled;
+    }
+
+    pub async fn enable_trading(&self) {
+        *self.trading_mode.lock().await = TradingMode::Enabled;
+    }
+
+    pub async fn is_trading_enabled(&self) -> bool {
+        *self.trading_mode.lock().await == TradingMode::Enabled
+    }
+
     pub async fn evaluate_trade(&self, trade: Trade) -> Result<(), RisklyError> {
         let start_time = Instant::now();
         let asset = trade.asset.clone();
@@ -45,6 +138,21 @@ impl RisklyService {
 
         let checks_start = Instant::now();
 
+        // Quantity must be a well-formed, non-negative number before anything else
+        if quantity.is_nan() || quantity < 0.0 {
+            return Err(RisklyError::InvalidQuantity(format!(
+                "Invalid trade quantity: {quantity}"
+            )));
+        }
+
+        // 0. Is trading allowed at all right now?
+        let mode = *self.trading_mode.lock().await;
+        if mode == TradingMode::Disabled {
+            return Err(RisklyError::TradingDisabled(
+                "trading is disabled".to_string(),
+            ));
+        }
+
         // 1. Is the asset allowed?
         let asset_check_start = Instant::now();
         if !self.config.allowed_assets.contains(&asset) {
@@ -78,10 +186,30 @@ impl RisklyService {
             }
         };
 
+        if mode == TradingMode::ReduceOnly && new_position.abs() >= current_position.abs() {
+            return Err(RisklyError::ReduceOnlyViolation(format!(
+                "Trading is in reduce-only mode: {current_position} -> {new_position} for {asset} does not reduce exposure"
+            )));
+        }
+
+        // Worst-case fill of same-side resting orders, reserved against risk budget
+        let reserved: f64 = state
+            .open_orders
+            .iter()
+            .filter(|order| order.asset == asset && order.side == trade.side)
+            .map(|order| order.quantity)
+            .sum();
+
+        let projected_with_reserved = match trade.side {
+            0 => current_position + quantity + reserved,
+            1 => current_position - quantity - reserved,
+            _ => unreachable!("trade.side already validated above"),
+        };
+
         if let Some(max_position) = self.config.max_position_per_asset.get(&asset) {
-            if new_position.abs() > *max_position {
+            if projected_with_reserved.abs() > *max_position {
                 return Err(RisklyError::ExceedsMaxPosition(format!(
-                    "Projected position {new_position} exceeds max {max_position} for {asset}"
+                    "Projected position {projected_with_reserved} (incl. {reserved} reserved in open orders) exceeds max {max_position} for {asset}"
                 )));
             }
         }
@@ -91,7 +219,7 @@ impl RisklyService {
         let volume_check_start = Instant::now();
         let current_volume = state.daily_volume.get(&asset).cloned().unwrap_or(0.0);
 
-        let projected_volume = current_volume + quantity;
+        let projected_volume = current_volume + quantity + reserved;
 
         if let Some(max_volume) = self.config.max_daily_volume.get(&asset) {
             if &projected_volume > max_volume {
@@ -102,11 +230,54 @@ impl RisklyService {
         }
         let volume_check_duration = volume_check_start.elapsed();
 
+        // 5. Check projected allocation against portfolio value, if a mark price is known
+        let allocation_check_start = Instant::now();
+        if let Some(mark_price) = state.last_prices.get(&asset) {
+            if let Some(max_allocation_pct) = self.config.max_allocation_per_asset_pct.get(&asset)
+            {
+                let portfolio_value: f64 = state
+                    .current_positions
+                    .iter()
+                    .filter_map(|(a, qty)| state.last_prices.get(a).map(|price| qty.abs() * price))
+                    .sum();
+
+                if portfolio_value > 0.0 {
+                    let projected_notional = new_position.abs() * mark_price;
+                    let allocation_pct = projected_notional / portfolio_value * 100.0;
+
+                    if allocation_pct > *max_allocation_pct {
+                        return Err(RisklyError::ExceedsMaxAllocation(format!(
+                            "Projected allocation {allocation_pct:.2}% exceeds max {max_allocation_pct}% for {asset}"
+                        )));
+                    }
+                }
+            }
+        }
+        let allocation_check_duration = allocation_check_start.elapsed();
+
+        // 6. Check slippage against mark price, if both a mark and an exec price are known
+        let slippage_check_start = Instant::now();
+        if let (Some(mark_price), Some(exec_price)) =
+            (state.last_prices.get(&asset), trade.exec_price)
+        {
+            if *mark_price != 0.0 {
+                let slippage_pct = (exec_price - mark_price).abs() / mark_price * 100.0;
+
+                if slippage_pct > self.config.max_slippage_pct {
+                    return Err(RisklyError::ExceedsMaxSlippage(format!(
+                        "Slippage {slippage_pct:.2}% exceeds max {}% for {asset}",
+                        self.config.max_slippage_pct
+                    )));
+                }
+            }
+        }
+        let slippage_check_duration = slippage_check_start.elapsed();
+
         let checks_duration = checks_start.elapsed();
         let total_duration = start_time.elapsed();
 
         println!(
-            "evaluate_trade business logic for {asset}: total={total_duration:?}, state_lock={state_lock_duration:?}, checks={checks_duration:?} (asset={asset_check_duration:?}, size={size_check_duration:?}, position={position_check_duration:?},  volume={volume_check_duration:?})",
+            "evaluate_trade business logic for {asset}: total={total_duration:?}, state_lock={state_lock_duration:?}, checks={checks_duration:?} (asset={asset_check_duration:?}, size={size_check_duration:?}, position={position_check_duration:?},  volume={volume_check_duration:?}, allocation={allocation_check_duration:?}, slippage={slippage_check_duration:?})",
         );
 
         // If all checks pass
@@ -115,7 +286,26 @@ impl RisklyService {
 
     pub async fn add_trade(&self, trade: Trade) -> Result<(), RisklyError> {
         // need to first evaluate the trade.
-        self.evaluate_trade(trade.clone()).await?;
+        if let Err(err) = self.evaluate_trade(trade.clone()).await {
+            let current_position = self
+                .state
+                .lock()
+                .await
+                .current_positions
+                .get(&trade.asset)
+                .cloned()
+                .unwrap_or(0.0);
+
+            self.log_activity(
+                trade.asset.clone(),
+                trade.side,
+                trade.quantity,
+                Decision::Rejected(err.to_string()),
+                current_position,
+            );
+
+            return Err(err);
+        }
 
         // Need to update state in this function.
 
@@ -165,10 +355,306 @@ impl RisklyService {
             .daily_volume
             .insert(trade.asset.clone(), current_volume + trade.quantity);
 
+        let resulting_position = current_state
+            .current_positions
+            .get(&trade.asset)
+            .cloned()
+            .unwrap_or(0.0);
+
         if let Err(error) = self.state_tx.send(Ok(current_state.clone())) {
             println!("Channel send error {error:?}");
         };
 
+        // Release the state lock before the synchronous file I/O in log_activity so
+        // other evaluations aren't blocked on disk while this one merely logs.
+        drop(current_state);
+
+        self.log_activity(
+            trade.asset.clone(),
+            trade.side,
+            trade.quantity,
+            Decision::Allowed,
+            resulting_position,
+        );
+
+        Ok(())
+    }
+
+    pub async fn add_order(&self, order: OpenOrder) -> Result<(), RisklyError> {
+        if order.side != 0 && order.side != 1 {
+            return Err(RisklyError::InvalidTradeSide(format!(
+                "Unknown order side: {}",
+                order.side
+            )));
+        }
+
+        let mut current_state = self.state.lock().await;
+        current_state.open_orders.push(order);
+
+        if let Err(error) = self.state_tx.send(Ok(current_state.clone())) {
+            println!("Channel send error {error:?}");
+        }
+
         Ok(())
     }
+
+    pub async fn remove_order(&self, order_id: String) {
+        let mut current_state = self.state.lock().await;
+        current_state
+            .open_orders
+            .retain(|order| order.id != order_id);
+
+        if let Err(error) = self.state_tx.send(Ok(current_state.clone())) {
+            println!("Channel send error {error:?}");
+        }
+    }
+
+    pub async fn update_market_value(&self, update: PriceUpdateRequest) {
+        let mut current_state = self.state.lock().await;
+
+        let timestamp = update.timestamp.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as i64)
+                .unwrap_or_default()
+        });
+
+        current_state
+            .last_prices
+            .insert(update.asset.clone(), update.price);
+        current_state.mark_timestamps.insert(update.asset, timestamp);
+
+        if let Err(error) = self.state_tx.send(Ok(current_state.clone())) {
+            println!("Channel send error {error:?}");
+        }
+    }
+}
+
+/// Computes the next `daily_reset_time` boundary in `reset_timezone` that is
+/// strictly after now. An unknown `reset_timezone` falls back to UTC. Returns
+/// `None` if `daily_reset_time` is unparseable or the boundary's local time is
+/// ambiguous or nonexistent (a DST transition).
+fn next_reset_boundary(daily_reset_time: &str, reset_timezone: &str) -> Option<chrono::DateTime<Tz>> {
+    let tz: Tz = match reset_timezone.parse() {
+        Ok(tz) => tz,
+        Err(_) => {
+            println!("Unknown reset_timezone '{reset_timezone}', falling back to UTC");
+            chrono_tz::UTC
+        }
+    };
+
+    let Ok(reset_time) = NaiveTime::parse_from_str(daily_reset_time, "%H:%M") else {
+        println!("Unparseable daily_reset_time '{daily_reset_time}'");
+        return None;
+    };
+
+    let now = chrono::Utc::now().with_timezone(&tz);
+
+    let mut next = tz
+        .from_local_datetime(&now.date_naive().and_time(reset_time))
+        .single();
+
+    if next.map(|next| next <= now).unwrap_or(true) {
+        next = tz
+            .from_local_datetime(&(now.date_naive() + chrono::Duration::days(1)).and_time(reset_time))
+            .single();
+    }
+
+    next
+}
+
+/// Computes how long to sleep before the next `daily_reset_time` boundary in
+/// `reset_timezone`, falling back to a straight 24h when `next_reset_boundary`
+/// yields no boundary (an unknown timezone is treated as UTC, not as a fallback).
+fn duration_until_next_reset(daily_reset_time: &str, reset_timezone: &str) -> Duration {
+    let fallback = Duration::from_secs(24 * 60 * 60);
+
+    let Some(next) = next_reset_boundary(daily_reset_time, reset_timezone) else {
+        return fallback;
+    };
+
+    let now = chrono::Utc::now().with_timezone(&next.timezone());
+
+    (next - now).to_std().unwrap_or(fallback)
+}
+
+/// Start (as unix millis) of the reset window currently in effect: the most
+/// recent `daily_reset_time` boundary in `reset_timezone` that is not in the
+/// future. Used to bound activity-log replay to *today's* volume, since
+/// `daily_volume` is a single-day counter, not an all-time total.
+fn start_of_current_reset_window(daily_reset_time: &str, reset_timezone: &str) -> i64 {
+    let fallback = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as i64 - Duration::from_secs(24 * 60 * 60).as_millis() as i64)
+            .unwrap_or(0)
+    };
+
+    let Some(next) = next_reset_boundary(daily_reset_time, reset_timezone) else {
+        return fallback();
+    };
+
+    (next - chrono::Duration::days(1)).timestamp_millis()
+}
+
+/// Resets `daily_volume` at the configured wall-clock boundary, recomputing the
+/// next boundary each cycle (rather than a fixed 24h) so DST transitions don't drift it.
+fn spawn_daily_reset_task(
+    state: Arc<Mutex<RisklyState>>,
+    state_tx: watch::Sender<Result<RisklyState, tonic::Status>>,
+    daily_reset_time: String,
+    reset_timezone: String,
+) {
+    tokio::spawn(async move {
+        loop {
+            let wait = duration_until_next_reset(&daily_reset_time, &reset_timezone);
+            tokio::time::sleep(wait).await;
+
+            let mut current_state = state.lock().await;
+            current_state.daily_volume.clear();
+
+            if let Err(error) = state_tx.send(Ok(current_state.clone())) {
+                println!("Channel send error {error:?}");
+            }
+            drop(current_state);
+
+            println!("Daily limits reset at {daily_reset_time} {reset_timezone}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AbuseConfig;
+
+    fn test_config() -> RisklyConfig {
+        RisklyConfig {
+            max_position_per_asset: HashMap::new(),
+            max_trade_size: HashMap::new(),
+            max_daily_volume: HashMap::new(),
+            max_allocation_per_asset_pct: HashMap::new(),
+            allowed_assets: vec!["BTC".to_string()],
+            max_slippage_pct: 100.0,
+            trading_mode: TradingMode::ReduceOnly,
+            daily_reset_time: "00:00".to_string(),
+            reset_timezone: "UTC".to_string(),
+            activity_log_path: None,
+            replay_activity_log_on_startup: false,
+            abuse: AbuseConfig {
+                error_threshold: 5,
+                window_secs: 60,
+                cooldown_secs: 300,
+            },
+            price_feed: None,
+            listen_address: "127.0.0.1:0".to_string(),
+        }
+    }
+
+    fn test_trade(side: i32, quantity: f64) -> Trade {
+        Trade {
+            asset: "BTC".to_string(),
+            quantity,
+            side,
+            exec_price: None,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_rejects_a_trade_that_grows_the_position() {
+        let service = RisklyService::new(test_config());
+        service
+            .state
+            .lock()
+            .await
+            .current_positions
+            .insert("BTC".to_string(), 1.0);
+
+        // side 0 (buy) on a long position grows exposure, which reduce-only forbids
+        let result = service.evaluate_trade(test_trade(0, 0.5)).await;
+
+        assert!(matches!(result, Err(RisklyError::ReduceOnlyViolation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_allows_a_trade_that_shrinks_the_position() {
+        let service = RisklyService::new(test_config());
+        service
+            .state
+            .lock()
+            .await
+            .current_positions
+            .insert("BTC".to_string(), 1.0);
+
+        // side 1 (sell) on a long position shrinks exposure toward zero
+        let result = service.evaluate_trade(test_trade(1, 0.5)).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_rejects_a_trade_that_flips_past_flat() {
+        let service = RisklyService::new(test_config());
+        service
+            .state
+            .lock()
+            .await
+            .current_positions
+            .insert("BTC".to_string(), 1.0);
+
+        // selling past flat swaps a 1.0 long for a 1.5 short: |new| >= |current|
+        let result = service.evaluate_trade(test_trade(1, 2.5)).await;
+
+        assert!(matches!(result, Err(RisklyError::ReduceOnlyViolation(_))));
+    }
+
+    #[test]
+    fn test_duration_until_next_reset_is_bounded_by_24h() {
+        let duration = duration_until_next_reset("00:00", "UTC");
+        assert!(duration > Duration::from_secs(0));
+        assert!(duration <= Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn test_duration_until_next_reset_targets_the_upcoming_boundary() {
+        let now = chrono::Utc::now().with_timezone(&chrono_tz::UTC);
+        let target = now + chrono::Duration::minutes(1);
+        let daily_reset_time = target.format("%H:%M").to_string();
+
+        let duration = duration_until_next_reset(&daily_reset_time, "UTC");
+
+        // The boundary is whatever wall-clock minute is 1 minute out; allow enough
+        // slack either side for the two `Utc::now()` calls (here and inside the
+        // function under test) to land in different seconds.
+        assert!(duration <= Duration::from_secs(61));
+    }
+
+    #[test]
+    fn test_duration_until_next_reset_falls_back_to_24h_on_unparseable_time() {
+        let duration = duration_until_next_reset("not-a-time", "UTC");
+        assert_eq!(duration, Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn test_duration_until_next_reset_falls_back_to_utc_on_unknown_timezone() {
+        // An unknown timezone degrades to UTC rather than failing outright, so this
+        // should behave identically to the `"UTC"` case, not hit the 24h fallback.
+        let duration = duration_until_next_reset("00:00", "Not/A_Timezone");
+        assert!(duration > Duration::from_secs(0));
+        assert!(duration <= Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn test_start_of_current_reset_window_precedes_now() {
+        let now_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+
+        let window_start = start_of_current_reset_window("00:00", "UTC");
+
+        assert!(window_start <= now_millis);
+        assert!(now_millis - window_start <= 24 * 60 * 60 * 1000);
+    }
 }